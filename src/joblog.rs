@@ -0,0 +1,54 @@
+//! End-of-run summary report, inspired by GNU parallel's `--joblog`.
+//!
+//! Unlike the per-tick sample rows, the joblog holds a single row per
+//! monitored path, written once the monitored command exits, capturing the
+//! whole run at a glance.
+
+use std::fs::File;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct JobLogEntry {
+    pub path: String,
+    pub command: String,
+    pub start_time: f64,
+    pub runtime_ms: u128,
+    pub exit_code: i32,
+    pub initial_size: isize,
+    pub final_size: isize,
+    pub delta: isize,
+    pub peak: isize,
+    pub max_rate_bps: f64,
+}
+
+/// Seconds since the Unix epoch, matching the `Starttime` column GNU
+/// parallel's joblog uses.
+pub fn unix_timestamp(time: SystemTime) -> f64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Writes one summary row per monitored path to a single joblog file.
+pub struct Writer {
+    inner: csv::Writer<File>,
+}
+impl Writer {
+    pub fn create(path: &Path) -> Result<Self> {
+        let inner = csv::WriterBuilder::default()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_writer(File::create(path)?);
+        Ok(Self { inner })
+    }
+
+    pub fn write(&mut self, entry: &JobLogEntry) -> Result<()> {
+        self.inner.serialize(entry)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
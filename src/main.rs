@@ -1,108 +1,335 @@
 use std::{
+    collections::HashMap,
     fs::File,
-    io::{Write, stdout},
+    io::{stdout, Write},
     path::Path,
     process::Command,
     thread,
     time::Instant,
 };
 
-use anyhow::{Result, bail};
+use anyhow::{bail, Result};
 use clap::Parser;
-use memchr::memchr;
 use serde::Serialize;
 
+mod joblog;
+mod process;
+mod throughput;
+mod units;
+mod walker;
+mod writer;
+
+use joblog::JobLogEntry;
+use process::ProcessSampler;
+use throughput::Throughput;
+use units::Units;
+use walker::SizeMode;
+use writer::{Format, OutputSink};
+
 #[derive(Serialize)]
 pub struct Row {
+    pub path: String,
     pub elapsed: u128,
     pub disk_usage: isize,
     pub delta: isize,
     pub peak: isize,
+    pub rss_bytes: u64,
+    pub cpu_pct: f32,
+    pub throughput_bps: f64,
+    pub eta_ms: Option<u128>,
 }
 impl Row {
-    pub fn new(elapsed: u128, disk_usage: isize, initial_disk_usage: isize, peak: isize) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        path: String,
+        elapsed: u128,
+        disk_usage: isize,
+        initial_disk_usage: isize,
+        peak: isize,
+        rss_bytes: u64,
+        cpu_pct: f32,
+        throughput_bps: f64,
+        eta_ms: Option<u128>,
+    ) -> Self {
         Self {
+            path,
             elapsed,
             disk_usage,
             delta: disk_usage - initial_disk_usage,
             peak,
+            rss_bytes,
+            cpu_pct,
+            throughput_bps,
+            eta_ms,
         }
     }
 }
 
-pub struct Monitor {
-    output: csv::Writer<Box<dyn Write + Send>>,
-    start_time: Instant,
+/// A `Row` rendered with human-readable sizes and `HH:MM:SS.mmm` elapsed time,
+/// used in place of `Row` when `--human` is passed.
+#[derive(Serialize)]
+pub struct HumanRow {
+    pub path: String,
+    pub elapsed: String,
+    pub disk_usage: String,
+    pub delta: String,
+    pub peak: String,
+    pub rss_bytes: String,
+    pub cpu_pct: String,
+    pub throughput: String,
+    pub eta: String,
+}
+impl HumanRow {
+    pub fn new(row: &Row, units: Units) -> Self {
+        Self {
+            path: row.path.clone(),
+            elapsed: units::format_elapsed(row.elapsed),
+            disk_usage: units::format_size(row.disk_usage, units),
+            delta: units::format_size(row.delta, units),
+            peak: units::format_size(row.peak, units),
+            rss_bytes: units::format_size(row.rss_bytes as isize, units),
+            cpu_pct: format!("{:.1}%", row.cpu_pct),
+            throughput: format!(
+                "{}/s",
+                units::format_size(row.throughput_bps as isize, units)
+            ),
+            eta: row
+                .eta_ms
+                .map(units::format_elapsed)
+                .unwrap_or_else(|| "-".to_string()),
+        }
+    }
+}
+
+/// Per-path tracking state: the directories passed via `--path` are sampled
+/// independently, each keeping its own baseline, peak, and throughput window.
+struct PathState {
     initial_disk_usage: isize,
     peak_disk_usage: isize,
+    throughput: Throughput,
+}
+impl PathState {
+    fn new(initial_disk_usage: isize, window: usize) -> Self {
+        Self {
+            initial_disk_usage,
+            peak_disk_usage: initial_disk_usage,
+            throughput: Throughput::new(window),
+        }
+    }
+}
+
+/// The trailing summary `Monitor::finish` writes when the output format is
+/// `json` (see `writer::OutputSink`).
+#[derive(Serialize)]
+pub struct Summary {
+    pub elapsed_ms: u128,
+    pub max_rate_bps: f64,
+    pub peaks: HashMap<String, isize>,
+}
+
+pub struct Monitor {
+    output: OutputSink,
+    start_time: Instant,
+    paths: HashMap<String, PathState>,
+    units: Option<Units>,
+    window: usize,
+    target_size: Option<isize>,
+    max_rate_bps: f64,
 }
 impl Monitor {
-    pub fn new(writer: Box<dyn Write + Send>, initial_disk_usage: isize) -> Self {
-        let output = csv::WriterBuilder::default()
-            .delimiter(b'\t')
-            .has_headers(true)
-            .from_writer(writer);
+    pub fn new(
+        writer: Box<dyn Write + Send>,
+        format: Format,
+        initial_disk_usage: HashMap<String, isize>,
+        units: Option<Units>,
+        window: usize,
+        target_size: Option<isize>,
+    ) -> Self {
+        let output = OutputSink::new(format, writer);
+        let paths = initial_disk_usage
+            .into_iter()
+            .map(|(path, size)| (path, PathState::new(size, window)))
+            .collect();
         Self {
             output,
             start_time: Instant::now(),
-            initial_disk_usage,
-            peak_disk_usage: initial_disk_usage,
+            paths,
+            units,
+            window,
+            target_size,
+            max_rate_bps: 0.0,
         }
     }
-    pub fn add_disk_usage(&mut self, size: isize) -> Result<()> {
+    pub fn add_disk_usage(
+        &mut self,
+        path: &str,
+        size: isize,
+        rss_bytes: u64,
+        cpu_pct: f32,
+    ) -> Result<()> {
         let elapsed = self.start_time.elapsed().as_millis();
-        self.peak_disk_usage = self.peak_disk_usage.max(size);
-        let row = Row::new(elapsed, size, self.initial_disk_usage, self.peak_disk_usage);
-        self.output.serialize(row)?;
-        self.output.flush()?;
+        let window = self.window;
+        let state = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| PathState::new(size, window));
+        state.peak_disk_usage = state.peak_disk_usage.max(size);
+        let throughput_bps = state.throughput.sample(elapsed, size);
+        self.max_rate_bps = self.max_rate_bps.max(throughput_bps);
+        let eta_ms = self
+            .target_size
+            .and_then(|target| Throughput::eta_ms(throughput_bps, size, target));
+        let row = Row::new(
+            path.to_string(),
+            elapsed,
+            size,
+            state.initial_disk_usage,
+            state.peak_disk_usage,
+            rss_bytes,
+            cpu_pct,
+            throughput_bps,
+            eta_ms,
+        );
+        match self.units {
+            Some(units) => self.output.write_row(&HumanRow::new(&row, units))?,
+            None => self.output.write_row(&row)?,
+        }
         Ok(())
     }
+
+    pub fn peak_disk_usage(&self, path: &str) -> isize {
+        self.paths
+            .get(path)
+            .map(|state| state.peak_disk_usage)
+            .unwrap_or_default()
+    }
+
+    pub fn max_rate_bps(&self) -> f64 {
+        self.max_rate_bps
+    }
+
+    /// Finalizes the output stream, writing a trailing summary for formats
+    /// that support one (currently only `json`).
+    pub fn finish(self) -> Result<()> {
+        let summary = Summary {
+            elapsed_ms: self.start_time.elapsed().as_millis(),
+            max_rate_bps: self.max_rate_bps,
+            peaks: self
+                .paths
+                .iter()
+                .map(|(path, state)| (path.clone(), state.peak_disk_usage))
+                .collect(),
+        };
+        self.output.finish(&summary)
+    }
+}
+
+fn get_disk_usage(path: &str, mode: SizeMode) -> Result<isize> {
+    walker::disk_usage(Path::new(path), mode)
 }
 
-fn get_disk_usage(path: &str) -> Result<isize> {
-    let cmd = Command::new("du").arg("-d").arg("0").arg(path).output()?;
-    let whitespace_idx = memchr(b'\t', &cmd.stdout).expect("Failed to find directory size");
-    let dir_size_text = std::str::from_utf8(&cmd.stdout[..whitespace_idx])?;
-    let dir_size = dir_size_text.parse()?;
-    Ok(dir_size)
+fn sample_all_paths(paths: &[String], mode: SizeMode) -> Result<HashMap<String, isize>> {
+    paths
+        .iter()
+        .map(|path| Ok((path.clone(), get_disk_usage(path, mode)?)))
+        .collect()
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
 
-    let directory = Path::new(&args.path);
-    if !directory.exists() {
-        bail!("Provided directory ({}) does not exist", &args.path);
-    }
-    if !directory.is_dir() {
-        bail!("Provided path ({}) is not a directory", &args.path);
+    for path in &args.path {
+        let directory = Path::new(path);
+        if !directory.exists() {
+            bail!("Provided directory ({path}) does not exist");
+        }
+        if !directory.is_dir() {
+            bail!("Provided path ({path}) is not a directory");
+        }
     }
     let output_handle = args.output_handle()?;
+    let size_mode = args.size_mode();
 
     // Initialize the monitor
-    let initial_disk_usage = get_disk_usage(&args.path)?;
-    let mut monitor = Monitor::new(output_handle, initial_disk_usage);
+    let initial_disk_usage = sample_all_paths(&args.path, size_mode)?;
+    let mut monitor = Monitor::new(
+        output_handle,
+        args.format,
+        initial_disk_usage.clone(),
+        args.units(),
+        args.window,
+        args.target_size,
+    );
+
+    let start_time = Instant::now();
+    let wall_clock_start = std::time::SystemTime::now();
 
     // Start the child process
     let mut child = Command::new("sh").arg("-c").arg(&args.command).spawn()?;
+    let mut sampler = ProcessSampler::new(child.id());
 
     // Start the monitoring thread
-    let monitor = thread::spawn(move || -> Result<()> {
+    type RunOutcome = (
+        std::process::ExitStatus,
+        HashMap<String, isize>,
+        HashMap<String, isize>,
+        f64,
+    );
+    let loop_paths = args.path.clone();
+    let rate = args.rate;
+    let monitor = thread::spawn(move || -> Result<RunOutcome> {
         // Loop until the child process exits
-        while child.try_wait()?.is_none() {
-            let dir_size = get_disk_usage(&args.path)?;
-            monitor.add_disk_usage(dir_size)?;
-            std::thread::sleep(std::time::Duration::from_millis(args.rate));
-        }
+        let exit_status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            let (rss_bytes, cpu_pct) = sampler.sample();
+            for path in &loop_paths {
+                let dir_size = get_disk_usage(path, size_mode)?;
+                monitor.add_disk_usage(path, dir_size, rss_bytes, cpu_pct)?;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(rate));
+        };
 
-        let dir_size = get_disk_usage(&args.path)?;
-        monitor.add_disk_usage(dir_size)?;
+        let (rss_bytes, cpu_pct) = sampler.sample();
+        let mut final_disk_usage = HashMap::new();
+        for path in &loop_paths {
+            let dir_size = get_disk_usage(path, size_mode)?;
+            monitor.add_disk_usage(path, dir_size, rss_bytes, cpu_pct)?;
+            final_disk_usage.insert(path.clone(), dir_size);
+        }
+        let peak_disk_usage = loop_paths
+            .iter()
+            .map(|path| (path.clone(), monitor.peak_disk_usage(path)))
+            .collect();
+        let max_rate_bps = monitor.max_rate_bps();
+        monitor.finish()?;
 
-        Ok(())
+        Ok((exit_status, final_disk_usage, peak_disk_usage, max_rate_bps))
     });
-    monitor.join().unwrap()?;
+    let (exit_status, final_disk_usage, peak_disk_usage, max_rate_bps) = monitor.join().unwrap()?;
 
-    Ok(())
+    if let Some(joblog_path) = &args.joblog {
+        let mut writer = joblog::Writer::create(Path::new(joblog_path))?;
+        for path in &args.path {
+            let initial = initial_disk_usage[path];
+            let final_size = final_disk_usage[path];
+            writer.write(&JobLogEntry {
+                path: path.clone(),
+                command: args.command.clone(),
+                start_time: joblog::unix_timestamp(wall_clock_start),
+                runtime_ms: start_time.elapsed().as_millis(),
+                exit_code: exit_status.code().unwrap_or(-1),
+                initial_size: initial,
+                final_size,
+                delta: final_size - initial,
+                peak: peak_disk_usage[path],
+                max_rate_bps,
+            })?;
+        }
+    }
+
+    std::process::exit(exit_status.code().unwrap_or(1));
 }
 
 #[derive(Parser)]
@@ -114,13 +341,45 @@ pub struct Cli {
     #[clap(short, long, default_value = "100")]
     pub rate: u64,
 
-    /// The path to the directory to measure disk usage for
+    /// The path to the directory to measure disk usage for (repeatable)
     #[clap(short, long, default_value = ".")]
-    pub path: String,
+    pub path: Vec<String>,
 
     /// The path to the output [default: stdout]
     #[clap(short, long)]
     pub output: Option<String>,
+
+    /// Report the logical byte length of each file (default)
+    #[clap(long, conflicts_with = "disk_size")]
+    pub apparent_size: bool,
+
+    /// Report the actual space allocated on disk (`blocks * 512`) instead of apparent size
+    #[clap(long, conflicts_with = "apparent_size")]
+    pub disk_size: bool,
+
+    /// Format sizes and elapsed time for humans instead of emitting raw numbers
+    #[clap(long)]
+    pub human: bool,
+
+    /// The prefix family to use when formatting sizes with `--human`
+    #[clap(long, value_enum, default_value = "binary")]
+    pub units: Units,
+
+    /// The number of recent samples to average throughput over
+    #[clap(long, default_value = "10")]
+    pub window: usize,
+
+    /// An expected final directory size (in bytes) used to estimate an ETA
+    #[clap(long)]
+    pub target_size: Option<isize>,
+
+    /// Write a single end-of-run summary row to this path
+    #[clap(long)]
+    pub joblog: Option<String>,
+
+    /// The output format for per-tick samples
+    #[clap(long, value_enum, default_value = "tsv")]
+    pub format: Format,
 }
 impl Cli {
     pub fn output_handle(&self) -> Result<Box<dyn Write + Send>> {
@@ -129,4 +388,16 @@ impl Cli {
             None => Ok(Box::new(stdout())),
         }
     }
+
+    pub fn size_mode(&self) -> SizeMode {
+        if self.disk_size {
+            SizeMode::Disk
+        } else {
+            SizeMode::Apparent
+        }
+    }
+
+    pub fn units(&self) -> Option<Units> {
+        self.human.then_some(self.units)
+    }
 }
@@ -0,0 +1,95 @@
+//! Samples CPU and memory usage of a process tree.
+//!
+//! `sh -c <command>` usually forks a real worker process, so we can't just
+//! look at the immediate child PID: each tick we walk the full process list
+//! and follow parent-PID links to find every descendant of the root PID,
+//! then sum their RSS and CPU%.
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+pub struct ProcessSampler {
+    system: System,
+    root: Pid,
+    tracked: Vec<Pid>,
+}
+
+impl ProcessSampler {
+    pub fn new(root_pid: u32) -> Self {
+        let mut system = System::new_with_specifics(
+            RefreshKind::new().with_processes(ProcessRefreshKind::everything()),
+        );
+        // sysinfo only reports non-zero `cpu_usage()` once a process has been
+        // refreshed twice, `MINIMUM_CPU_UPDATE_INTERVAL` apart. Doing this
+        // refresh here means the counters already have a baseline by the
+        // time the first `sample()` call happens, rather than reporting 0.0
+        // for short-lived commands.
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::everything(),
+        );
+        let root = Pid::from_u32(root_pid);
+        let tracked = descendants_of(&system, root);
+        Self {
+            system,
+            root,
+            tracked,
+        }
+    }
+
+    /// Refreshes process info and returns the summed `(rss_bytes, cpu_pct)`
+    /// over the root PID and all of its descendants.
+    pub fn sample(&mut self) -> (u64, f32) {
+        // Cheap refresh (no CPU/memory accounting) of the whole process list,
+        // just to pick up on any new descendants that have forked since the
+        // last tick.
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new(),
+        );
+        self.tracked = descendants_of(&self.system, self.root);
+
+        // The actual (more expensive) CPU/memory refresh is narrowed to just
+        // the processes we care about.
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&self.tracked),
+            true,
+            ProcessRefreshKind::everything(),
+        );
+
+        let mut rss_bytes = 0;
+        let mut cpu_pct = 0.0;
+        for pid in &self.tracked {
+            if let Some(process) = self.system.process(*pid) {
+                rss_bytes += process.memory();
+                cpu_pct += process.cpu_usage();
+            }
+        }
+        (rss_bytes, cpu_pct)
+    }
+}
+
+/// Returns the root PID plus every process whose parent chain leads back to
+/// it.
+fn descendants_of(system: &System, root: Pid) -> Vec<Pid> {
+    let mut tree = vec![root];
+    loop {
+        let mut grew = false;
+        for (pid, process) in system.processes() {
+            if tree.contains(pid) {
+                continue;
+            }
+            if let Some(parent) = process.parent() {
+                if tree.contains(&parent) {
+                    tree.push(*pid);
+                    grew = true;
+                }
+            }
+        }
+        if !grew {
+            break;
+        }
+    }
+    tree
+}
@@ -0,0 +1,52 @@
+//! Moving-average throughput and ETA estimation.
+//!
+//! Instantaneous deltas between consecutive samples are noisy, so the rate
+//! is smoothed over a sliding window of recent `(elapsed_ms, disk_usage)`
+//! samples, the same moving-average approach disktest uses for its rate
+//! display.
+
+use std::collections::VecDeque;
+
+pub struct Throughput {
+    window: usize,
+    samples: VecDeque<(u128, isize)>,
+}
+
+impl Throughput {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: VecDeque::with_capacity(window.max(1)),
+        }
+    }
+
+    /// Records a new sample and returns the bytes/sec rate averaged over the
+    /// configured window.
+    pub fn sample(&mut self, elapsed_ms: u128, disk_usage: isize) -> f64 {
+        self.samples.push_back((elapsed_ms, disk_usage));
+        while self.samples.len() > self.window {
+            self.samples.pop_front();
+        }
+
+        let (oldest_elapsed, oldest_usage) = *self.samples.front().unwrap();
+        let dt_secs = (elapsed_ms - oldest_elapsed) as f64 / 1000.0;
+        if dt_secs <= 0.0 {
+            return 0.0;
+        }
+        (disk_usage - oldest_usage) as f64 / dt_secs
+    }
+
+    /// Estimates milliseconds remaining to reach `target_size` at the given
+    /// `rate_bps`, or `None` if the rate isn't positive (can't estimate).
+    /// Returns `Some(0)` once the target has already been reached.
+    pub fn eta_ms(rate_bps: f64, disk_usage: isize, target_size: isize) -> Option<u128> {
+        if rate_bps <= 0.0 {
+            return None;
+        }
+        let remaining = (target_size - disk_usage) as f64;
+        if remaining <= 0.0 {
+            return Some(0);
+        }
+        Some((remaining / rate_bps * 1000.0) as u128)
+    }
+}
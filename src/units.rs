@@ -0,0 +1,56 @@
+//! Human-readable formatting for byte sizes and elapsed time.
+//!
+//! Mirrors erdtree's `prefix` module: scale a byte count to the largest
+//! prefix where the value is still >= 1, using either a 1024-based binary
+//! scale or a 1000-based SI scale.
+
+use clap::ValueEnum;
+
+/// Which prefix family to use when formatting sizes with `--human`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Units {
+    /// 1024-based scale: KiB, MiB, GiB, ...
+    Binary,
+    /// 1000-based scale: KB, MB, GB, ...
+    Si,
+}
+
+const BINARY_PREFIXES: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const SI_PREFIXES: [&str; 7] = ["B", "KB", "MB", "GB", "TB", "PB", "EB"];
+
+/// Formats a (possibly negative) byte count using the given prefix family,
+/// scaling to the largest prefix where the magnitude is >= 1.
+pub fn format_size(bytes: isize, units: Units) -> String {
+    let (base, prefixes) = match units {
+        Units::Binary => (1024_f64, BINARY_PREFIXES),
+        Units::Si => (1000_f64, SI_PREFIXES),
+    };
+
+    let sign = if bytes < 0 { "-" } else { "" };
+    let mut value = bytes.unsigned_abs() as f64;
+    let mut idx = 0;
+    while value >= base && idx < prefixes.len() - 1 {
+        value /= base;
+        idx += 1;
+    }
+
+    let decimals = if idx == 0 {
+        0
+    } else if value < 10.0 {
+        2
+    } else {
+        1
+    };
+    format!("{sign}{value:.decimals$}{}", prefixes[idx])
+}
+
+/// Formats a millisecond duration as `HH:MM:SS.mmm`.
+pub fn format_elapsed(elapsed_ms: u128) -> String {
+    let ms = elapsed_ms % 1000;
+    let total_secs = elapsed_ms / 1000;
+    let secs = total_secs % 60;
+    let total_mins = total_secs / 60;
+    let mins = total_mins % 60;
+    let hours = total_mins / 60;
+    format!("{hours:02}:{mins:02}:{secs:02}.{ms:03}")
+}
@@ -0,0 +1,86 @@
+//! Native, cross-platform directory size traversal.
+//!
+//! Replaces shelling out to `du` with a recursive walk that sums file sizes
+//! directly, following the approach erdtree's `disk`/`inode` modules use to
+//! avoid double-counting hardlinks.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use walkdir::WalkDir;
+
+/// Which notion of "size" to report for each file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeMode {
+    /// Logical byte length of the file (`st_size`).
+    #[default]
+    Apparent,
+    /// Actual space allocated on disk (`st_blocks * 512`).
+    Disk,
+}
+
+/// Recursively sums the size of every regular file under `path`, counting
+/// each physical file (by device/inode) only once even if it is reachable
+/// through multiple hardlinks.
+pub fn disk_usage(path: &Path, mode: SizeMode) -> Result<isize> {
+    let mut seen = HashSet::new();
+    let mut total: isize = 0;
+
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if !is_first_occurrence(&metadata, &mut seen) {
+            continue;
+        }
+        total += file_size(&metadata, mode) as isize;
+    }
+
+    Ok(total)
+}
+
+#[cfg(unix)]
+fn is_first_occurrence(metadata: &std::fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn is_first_occurrence(metadata: &std::fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    match (metadata.volume_serial_number(), metadata.file_index()) {
+        (Some(volume), Some(index)) => seen.insert((volume as u64, index)),
+        // Can't uniquely identify the file (e.g. on FAT volumes); don't dedupe it.
+        _ => true,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_first_occurrence(_metadata: &std::fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn file_size(metadata: &std::fs::Metadata, mode: SizeMode) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    match mode {
+        SizeMode::Apparent => metadata.size(),
+        SizeMode::Disk => metadata.blocks() * 512,
+    }
+}
+
+#[cfg(windows)]
+fn file_size(metadata: &std::fs::Metadata, mode: SizeMode) -> u64 {
+    use std::os::windows::fs::MetadataExt;
+    // Windows doesn't expose allocated block counts through `std`; fall back
+    // to the logical length for both modes.
+    let _ = mode;
+    metadata.file_size()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_size(metadata: &std::fs::Metadata, _mode: SizeMode) -> u64 {
+    metadata.len()
+}
@@ -0,0 +1,90 @@
+//! Output backends for `Monitor`.
+//!
+//! `Row`/`HumanRow` already derive `Serialize`, so the sampling loop doesn't
+//! need to know which backend is in use: it just calls `write_row`. TSV/CSV
+//! rows are flushed immediately for `tail -f`-style consumption; `jsonl`
+//! writes one object per line; `json` buffers every row and emits a single
+//! array followed by a trailing summary object once the run finishes.
+
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+/// The `--format` the user asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Tsv,
+    Csv,
+    Json,
+    Jsonl,
+}
+
+/// A sink that rows are written to as they're sampled.
+///
+/// `csv::Writer` carries a sizeable internal buffer, so it's boxed here to
+/// keep this enum's size close to its other (much smaller) variants.
+pub enum OutputSink {
+    Delimited(Box<csv::Writer<Box<dyn Write + Send>>>),
+    Jsonl(Box<dyn Write + Send>),
+    Json {
+        writer: Box<dyn Write + Send>,
+        rows: Vec<Value>,
+    },
+}
+
+impl OutputSink {
+    pub fn new(format: Format, writer: Box<dyn Write + Send>) -> Self {
+        match format {
+            Format::Tsv => Self::Delimited(Box::new(
+                csv::WriterBuilder::default()
+                    .delimiter(b'\t')
+                    .has_headers(true)
+                    .from_writer(writer),
+            )),
+            Format::Csv => Self::Delimited(Box::new(
+                csv::WriterBuilder::default()
+                    .delimiter(b',')
+                    .has_headers(true)
+                    .from_writer(writer),
+            )),
+            Format::Jsonl => Self::Jsonl(writer),
+            Format::Json => Self::Json {
+                writer,
+                rows: Vec::new(),
+            },
+        }
+    }
+
+    pub fn write_row<T: Serialize>(&mut self, row: &T) -> Result<()> {
+        match self {
+            Self::Delimited(writer) => {
+                writer.serialize(row)?;
+                writer.flush()?;
+            }
+            Self::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, row)?;
+                writeln!(writer)?;
+                writer.flush()?;
+            }
+            Self::Json { rows, .. } => rows.push(serde_json::to_value(row)?),
+        }
+        Ok(())
+    }
+
+    /// Finalizes the output. For `json`, writes the buffered rows as a
+    /// single array followed by the trailing summary object; the other
+    /// formats have already written everything incrementally.
+    pub fn finish<T: Serialize>(self, summary: &T) -> Result<()> {
+        if let Self::Json { mut writer, rows } = self {
+            serde_json::to_writer(&mut writer, &rows)?;
+            writeln!(writer)?;
+            serde_json::to_writer(&mut writer, summary)?;
+            writeln!(writer)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}